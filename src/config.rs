@@ -7,23 +7,59 @@ use std::fs::File;
 use std::io::Write;
 use toml;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
 use irc::client::data::Config as IrcConfig;
-use failure::Error;
+use failure::{format_err, Error};
 use std::fmt;
+use arc_swap::ArcSwap;
 use directories::BaseDirs;
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
 
 use super::VERSION;
 
+/// current configuration schema version; bumped whenever the on-disk layout
+/// changes in a way that needs migrating
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// a migration upgrading a config from one schema version to the next; it runs
+/// on the already-deserialized `Conf`, so it sees the document only through
+/// `Conf`'s fields and not through its raw keys
+type Migration = fn(&mut Conf) -> Result<(), Error>;
+
+/// ordered migration chain; `MIGRATIONS[v]` upgrades a version-`v` document to
+/// version `v + 1`
+const MIGRATIONS: &[Migration] = &[
+    migrate_0_to_1,
+];
+
+/// a single IRC network: its name, connection settings, and optional
+/// per-network overrides of the global `Features`/`Parameters` sections
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Network {
     pub name: String,
+    #[serde(rename = "connection")]
+    pub client: IrcConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub features: Option<Features>,
+    #[serde(rename = "parameters", skip_serializing_if = "Option::is_none")]
+    pub params: Option<Parameters>,
 }
 
 impl Default for Network {
     fn default() -> Self {
         Self {
             name: "default".into(),
+            client: default_client(),
+            features: None,
+            params: None,
         }
     }
 }
@@ -61,6 +97,7 @@ impl Default for DbType {
 pub struct Database {
     #[serde(rename = "type")]
     pub db_type: DbType,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
 }
 
@@ -84,48 +121,392 @@ impl Default for Parameters {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(default)]
+/// top-level configuration
+///
+/// The global `features`/`params`/`database` sections act as defaults; each
+/// entry in `networks` may override `features`/`params` and carries its own
+/// connection. Deserialization accepts both the modern `[[network]]` array and
+/// the legacy single `[connection]` + `[network]` form (treated as a
+/// one-element network list), so existing config files keep parsing.
+#[derive(Clone)]
 pub struct Conf {
-    pub network: Network,
+    pub schema_version: u64,
     pub features: Features,
-    #[serde(rename = "parameters")]
     pub params: Parameters,
     pub database: Database,
-    #[serde(rename = "connection")]
+    pub networks: Vec<Network>,
+}
+
+/// per-network view with overrides resolved against the global default sections
+#[derive(Clone)]
+pub struct NetworkContext {
+    pub name: String,
     pub client: IrcConfig,
+    pub features: Features,
+    pub params: Parameters,
+}
+
+/// intermediate used to accept both the legacy and multi-network layouts
+#[derive(Deserialize)]
+#[serde(default)]
+struct RawConf {
+    schema_version: u64,
+    features: Features,
+    #[serde(rename = "parameters")]
+    params: Parameters,
+    database: Database,
+    /// legacy single connection table
+    connection: Option<IrcConfig>,
+    /// either a `[network]` table (legacy) or a `[[network]]` array (modern)
+    network: Option<toml::Value>,
+}
+
+impl Default for RawConf {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            features: Features::default(),
+            params: Parameters::default(),
+            database: Database::default(),
+            connection: None,
+            network: None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Conf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawConf::deserialize(deserializer)?;
+        let networks = resolve_networks(raw.network, raw.connection)
+            .map_err(de::Error::custom)?;
+        Ok(Conf {
+            schema_version: raw.schema_version,
+            features: raw.features,
+            params: raw.params,
+            database: raw.database,
+            networks,
+        })
+    }
+}
+
+impl Serialize for Conf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // emit the legacy layout for a single un-overridden network so existing
+        // files round-trip unchanged; otherwise emit the `[[network]]` array
+        let single = match self.networks.as_slice() {
+            [n] if n.features.is_none() && n.params.is_none() => Some(n),
+            _ => None,
+        };
+
+        if let Some(n) = single {
+            #[derive(Serialize)]
+            struct NetworkName<'a> { name: &'a str }
+            #[derive(Serialize)]
+            struct Legacy<'a> {
+                schema_version: u64,
+                network: NetworkName<'a>,
+                features: &'a Features,
+                #[serde(rename = "parameters")]
+                params: &'a Parameters,
+                database: &'a Database,
+                connection: &'a IrcConfig,
+            }
+            Legacy {
+                schema_version: self.schema_version,
+                network: NetworkName { name: &n.name },
+                features: &self.features,
+                params: &self.params,
+                database: &self.database,
+                connection: &n.client,
+            }
+            .serialize(serializer)
+        } else {
+            #[derive(Serialize)]
+            struct Multi<'a> {
+                schema_version: u64,
+                features: &'a Features,
+                #[serde(rename = "parameters")]
+                params: &'a Parameters,
+                database: &'a Database,
+                #[serde(rename = "network")]
+                networks: &'a [Network],
+            }
+            Multi {
+                schema_version: self.schema_version,
+                features: &self.features,
+                params: &self.params,
+                database: &self.database,
+                networks: &self.networks,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+/// build the resolved network list from the raw `network` value and optional
+/// legacy `connection` table
+fn resolve_networks(
+    network: Option<toml::Value>,
+    connection: Option<IrcConfig>,
+) -> Result<Vec<Network>, Error> {
+    match network {
+        // modern form: an array of full network entries
+        Some(toml::Value::Array(entries)) => {
+            let networks = entries
+                .into_iter()
+                .map(|e| e.try_into())
+                .collect::<Result<Vec<Network>, _>>()?;
+            if networks.is_empty() {
+                Ok(vec![Network::default()])
+            } else {
+                Ok(networks)
+            }
+        },
+        // legacy form: a `[network]` table carrying just a name
+        Some(value) => {
+            let named: Network = Network {
+                name: value
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("default")
+                    .to_string(),
+                client: connection.unwrap_or_else(default_client),
+                features: None,
+                params: None,
+            };
+            Ok(vec![named])
+        },
+        // no network section: a single default (or legacy bare connection)
+        None => Ok(vec![Network {
+            name: "default".into(),
+            client: connection.unwrap_or_else(default_client),
+            features: None,
+            params: None,
+        }]),
+    }
 }
 
 impl Conf {
-    /// load configuration TOML from a file
+    /// load configuration from a file, choosing the serialization format from
+    /// the path extension (`.toml`, `.json`, `.yaml`/`.yml`; TOML otherwise) and
+    /// migrating the deserialized config from an older schema version up to the
+    /// current one (rewriting the upgraded file when `features.autosave` is
+    /// set)
     pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
-        let conf = fs::read_to_string(path.as_ref())?;
-        let conf: Conf = toml::de::from_str(&conf)?;
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path)?;
+        let format = Format::from_path(path);
+        let conf = format.parse(&raw)?;
+
+        if conf.features.autosave
+            && format.document_version(&raw)? < SCHEMA_VERSION
+        {
+            info!("migrated `{}` to schema version {}",
+                path.display(), SCHEMA_VERSION);
+            conf.write(path)?;
+        }
+
         Ok(conf)
     }
 
-    /// write configuration to a file
+    /// write configuration to a file, choosing the serialization format from
+    /// the path extension (TOML by default)
     pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let format = Format::from_path(path);
         let mut file = File::create(path)?;
-        file.write_all(toml::ser::to_string(&self)?.as_bytes())?;
+        file.write_all(format.serialize(self)?.as_bytes())?;
         Ok(())
     }
 
-    /// add an IRC channel to the list of channels in the configuration
-    pub fn add_channel(&mut self, name: String) {
-        if let Some(ref mut c) = self.client.channels {
-            if !c.contains(&name) {
-                c.push(name);
+    /// override configuration fields from `URLBOT_*` environment variables
+    ///
+    /// keys walk the struct hierarchy with `_` separators, e.g.
+    /// `URLBOT_CONNECTION_SERVER`, `URLBOT_PARAMETERS_URL_LIMIT`,
+    /// `URLBOT_FEATURES_REPORT_METADATA`; `Vec` fields are comma-split.
+    /// Unrecognized `URLBOT_*` keys are reported as warnings.
+    pub fn apply_env(&mut self, vars: impl Iterator<Item = (String, String)>) {
+        for (key, val) in vars {
+            let name = match key.strip_prefix("URLBOT_") {
+                Some(name) => name,
+                None => continue,
+            };
+            // CONNECTION_* / NETWORK_NAME target the default (first) network
+            let net = &mut self.networks[0];
+            match name {
+                "NETWORK_NAME" => net.name = val,
+
+                "CONNECTION_SERVER" => net.client.server = Some(val),
+                "CONNECTION_PASSWORD" => net.client.password = Some(val),
+                "CONNECTION_NICKNAME" => net.client.nickname = Some(val),
+                "CONNECTION_PORT" =>
+                    parse_env(&key, &val, |v| net.client.port = Some(v)),
+                "CONNECTION_USE_SSL" =>
+                    parse_env(&key, &val, |v| net.client.use_ssl = Some(v)),
+                "CONNECTION_CHANNELS" =>
+                    net.client.channels = Some(split_list(&val)),
+
+                "PARAMETERS_URL_LIMIT" =>
+                    parse_env(&key, &val, |v| self.params.url_limit = v),
+                "PARAMETERS_ACCEPT_LANG" => self.params.accept_lang = val,
+                "PARAMETERS_STATUS_CHANNELS" =>
+                    self.params.status_channels = split_list(&val),
+                "PARAMETERS_NICK_RESPONSE_STR" =>
+                    self.params.nick_response_str = val,
+
+                "FEATURES_REPORT_METADATA" =>
+                    parse_env(&key, &val, |v| self.features.report_metadata = v),
+                "FEATURES_REPORT_MIME" =>
+                    parse_env(&key, &val, |v| self.features.report_mime = v),
+                "FEATURES_MASK_HIGHLIGHTS" =>
+                    parse_env(&key, &val, |v| self.features.mask_highlights = v),
+                "FEATURES_SEND_NOTICE" =>
+                    parse_env(&key, &val, |v| self.features.send_notice = v),
+                "FEATURES_HISTORY" =>
+                    parse_env(&key, &val, |v| self.features.history = v),
+                "FEATURES_INVITE" =>
+                    parse_env(&key, &val, |v| self.features.invite = v),
+                "FEATURES_AUTOSAVE" =>
+                    parse_env(&key, &val, |v| self.features.autosave = v),
+                "FEATURES_SEND_ERRORS_TO_POSTER" =>
+                    parse_env(&key, &val, |v| self.features.send_errors_to_poster = v),
+                "FEATURES_REPLY_WITH_ERRORS" =>
+                    parse_env(&key, &val, |v| self.features.reply_with_errors = v),
+                "FEATURES_PARTIAL_URLS" =>
+                    parse_env(&key, &val, |v| self.features.partial_urls = v),
+                "FEATURES_NICK_RESPONSE" =>
+                    parse_env(&key, &val, |v| self.features.nick_response = v),
+
+                _ => warn!("ignoring unrecognized environment variable `{}`", key),
             }
         }
     }
 
-    /// remove an IRC channel from the list of channels in the configuration
+    /// apply a list of dotted-path `section.field=value` overrides
+    ///
+    /// e.g. `connection.server=irc.libera.chat`, `parameters.url_limit=3`,
+    /// `features.history=true`; `connection.*` and `network.name` target the
+    /// default network. Returns a descriptive error for unknown paths or values
+    /// that don't parse into the target field's type.
+    pub fn apply_overrides(&mut self, overrides: &[(String, String)])
+        -> Result<(), Error>
+    {
+        for (path, val) in overrides {
+            self.apply_override(path, val)?;
+        }
+        Ok(())
+    }
+
+    fn apply_override(&mut self, path: &str, val: &str) -> Result<(), Error> {
+        let net = &mut self.networks[0];
+        match path {
+            "network.name" => net.name = val.to_string(),
+
+            "connection.server" => net.client.server = Some(val.to_string()),
+            "connection.password" => net.client.password = Some(val.to_string()),
+            "connection.nickname" => net.client.nickname = Some(val.to_string()),
+            "connection.port" =>
+                net.client.port = Some(parse_override(path, val)?),
+            "connection.use_ssl" =>
+                net.client.use_ssl = Some(parse_override(path, val)?),
+            "connection.channels" =>
+                net.client.channels = Some(split_list(val)),
+
+            "parameters.url_limit" =>
+                self.params.url_limit = parse_override(path, val)?,
+            "parameters.accept_lang" => self.params.accept_lang = val.to_string(),
+            "parameters.status_channels" =>
+                self.params.status_channels = split_list(val),
+            "parameters.nick_response_str" =>
+                self.params.nick_response_str = val.to_string(),
+
+            "features.report_metadata" =>
+                self.features.report_metadata = parse_override(path, val)?,
+            "features.report_mime" =>
+                self.features.report_mime = parse_override(path, val)?,
+            "features.mask_highlights" =>
+                self.features.mask_highlights = parse_override(path, val)?,
+            "features.send_notice" =>
+                self.features.send_notice = parse_override(path, val)?,
+            "features.history" =>
+                self.features.history = parse_override(path, val)?,
+            "features.invite" =>
+                self.features.invite = parse_override(path, val)?,
+            "features.autosave" =>
+                self.features.autosave = parse_override(path, val)?,
+            "features.send_errors_to_poster" =>
+                self.features.send_errors_to_poster = parse_override(path, val)?,
+            "features.reply_with_errors" =>
+                self.features.reply_with_errors = parse_override(path, val)?,
+            "features.partial_urls" =>
+                self.features.partial_urls = parse_override(path, val)?,
+            "features.nick_response" =>
+                self.features.nick_response = parse_override(path, val)?,
+
+            "database.path" => self.database.path = Some(val.to_string()),
+
+            _ => return Err(format_err!("unknown config path `{}`", path)),
+        }
+        Ok(())
+    }
+
+    /// resolve each configured network against the global default sections
+    pub fn contexts(&self) -> Vec<NetworkContext> {
+        self.networks.iter().map(|n| NetworkContext {
+            name: n.name.clone(),
+            client: n.client.clone(),
+            features: n.features.clone().unwrap_or_else(|| self.features.clone()),
+            params: n.params.clone().unwrap_or_else(|| self.params.clone()),
+        }).collect()
+    }
+
+    /// add an IRC channel to the default network's channel list
+    pub fn add_channel(&mut self, name: String) {
+        if let Some(net) = self.networks.first_mut() {
+            add_channel(net, name);
+        }
+    }
+
+    /// remove an IRC channel from the default network's channel list
     pub fn remove_channel(&mut self, name: &str) {
-        if let Some(ref mut c) = self.client.channels {
-            if let Some(index) = c.iter().position(|c| c == name) {
-                c.remove(index);
-            }
+        if let Some(net) = self.networks.first_mut() {
+            remove_channel(net, name);
+        }
+    }
+
+    /// add an IRC channel to a named network's channel list
+    pub fn add_channel_to(&mut self, network: &str, name: String) {
+        if let Some(net) = self.networks.iter_mut().find(|n| n.name == network) {
+            add_channel(net, name);
+        }
+    }
+
+    /// remove an IRC channel from a named network's channel list
+    pub fn remove_channel_from(&mut self, network: &str, name: &str) {
+        if let Some(net) = self.networks.iter_mut().find(|n| n.name == network) {
+            remove_channel(net, name);
+        }
+    }
+}
+
+fn add_channel(net: &mut Network, name: String) {
+    if let Some(ref mut c) = net.client.channels {
+        if !c.contains(&name) {
+            c.push(name);
+        }
+    }
+}
+
+fn remove_channel(net: &mut Network, name: &str) {
+    if let Some(ref mut c) = net.client.channels {
+        if let Some(index) = c.iter().position(|c| c == name) {
+            c.remove(index);
         }
     }
 }
@@ -133,41 +514,168 @@ impl Conf {
 impl Default for Conf {
     fn default() -> Self {
         Self {
-            network: Network::default(),
+            schema_version: SCHEMA_VERSION,
             features: Features::default(),
             params: Parameters::default(),
             database: Database::default(),
-            client: IrcConfig {
-                nickname: Some("url-bot-rs".to_string()),
-                alt_nicks: Some(vec!["url-bot-rs_".to_string()]),
-                nick_password: Some("".to_string()),
-                username: Some("url-bot-rs".to_string()),
-                realname: Some("url-bot-rs".to_string()),
-                server: Some("127.0.0.1".to_string()),
-                port: Some(6667),
-                password: Some("".to_string()),
-                use_ssl: Some(false),
-                channels: Some(vec!["#url-bot-rs".to_string()]),
-                user_info: Some("Feed me URLs.".to_string()),
-                ..IrcConfig::default()
-            }
+            networks: vec![Network::default()],
+        }
+    }
+}
+
+/// upgrade a parsed `Conf` from its on-disk schema version up to the current
+/// one, running each intermediate migration in order, then stamping the
+/// current version. Versions newer than the current schema are refused.
+///
+/// Migrations operate on the fully-parsed `Conf` rather than a format-specific
+/// raw value, so every serialization backend (TOML/JSON/YAML) migrates through
+/// the same code path.
+fn migrate(conf: &mut Conf, from: u64) -> Result<(), Error> {
+    if from > SCHEMA_VERSION {
+        return Err(format_err!(
+            "config schema version {} is newer than the supported version \
+            {}; please upgrade url-bot-rs", from, SCHEMA_VERSION));
+    }
+
+    for v in from..SCHEMA_VERSION {
+        MIGRATIONS[v as usize](conf)?;
+    }
+
+    conf.schema_version = SCHEMA_VERSION;
+    Ok(())
+}
+
+/// version 0 (pre-versioning) to 1: the legacy single-`connection` layout is
+/// handled by `Conf`'s backward-compatible deserializer, so there is nothing
+/// structural to change for this step
+fn migrate_0_to_1(_conf: &mut Conf) -> Result<(), Error> {
+    Ok(())
+}
+
+/// lightweight probe to read a document's `schema_version` before defaults are
+/// applied (a missing version means the pre-versioning era, version 0)
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    schema_version: u64,
+}
+
+/// serialization format for a configuration file, selected by path extension
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// pick a format from a path's extension, defaulting to TOML
+    fn from_path(path: &Path) -> Self {
+        match path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("json") => Format::Json,
+            Some("yaml") | Some("yml") => Format::Yaml,
+            _ => Format::Toml,
         }
     }
+
+    /// deserialize a document straight into a `Conf` with the matching serde
+    /// backend; the JSON/YAML backends map `null`/`~` to `None` natively
+    fn deserialize(self, s: &str) -> Result<Conf, Error> {
+        Ok(match self {
+            Format::Toml => toml::de::from_str(s)?,
+            Format::Json => serde_json::from_str(s)?,
+            Format::Yaml => serde_yaml::from_str(s)?,
+        })
+    }
+
+    /// parse a document into a `Conf`, then migrate it from an older schema
+    /// version
+    fn parse(self, s: &str) -> Result<Conf, Error> {
+        let from = self.document_version(s)?;
+        let mut conf = self.deserialize(s)?;
+        migrate(&mut conf, from)?;
+        Ok(conf)
+    }
+
+    /// serialize a `Conf` into this format
+    fn serialize(self, conf: &Conf) -> Result<String, Error> {
+        Ok(match self {
+            Format::Toml => toml::ser::to_string(conf)?,
+            Format::Json => serde_json::to_string_pretty(conf)?,
+            Format::Yaml => serde_yaml::to_string(conf)?,
+        })
+    }
+
+    /// read the on-disk schema version of a document without applying defaults
+    fn document_version(self, s: &str) -> Result<u64, Error> {
+        let probe: VersionProbe = match self {
+            Format::Toml => toml::de::from_str(s)?,
+            Format::Json => serde_json::from_str(s)?,
+            Format::Yaml => serde_yaml::from_str(s)?,
+        };
+        Ok(probe.schema_version)
+    }
+}
+
+/// the default IRC client configuration used for a freshly-created network
+fn default_client() -> IrcConfig {
+    IrcConfig {
+        nickname: Some("url-bot-rs".to_string()),
+        alt_nicks: Some(vec!["url-bot-rs_".to_string()]),
+        nick_password: Some("".to_string()),
+        username: Some("url-bot-rs".to_string()),
+        realname: Some("url-bot-rs".to_string()),
+        server: Some("127.0.0.1".to_string()),
+        port: Some(6667),
+        password: Some("".to_string()),
+        use_ssl: Some(false),
+        channels: Some(vec!["#url-bot-rs".to_string()]),
+        user_info: Some("Feed me URLs.".to_string()),
+        ..IrcConfig::default()
+    }
 }
 
 // run time data structure. this is used to pass around mutable runtime data
 // where it's needed, including command line arguments, configuration file
 // settings, any parameters defined based on both of these sources, and
 // any other data used at runtime
-#[derive(Default, Clone)]
 pub struct Rtd {
     /// paths
     pub paths: Paths,
-    /// configuration file data
-    pub conf: Conf,
+    /// configuration file data, shared so the running IRC loop can observe
+    /// hot-reloads without reconnecting
+    pub conf: Arc<ArcSwap<Conf>>,
+    /// dotted-path `section.field=value` overrides from the command line
+    pub overrides: Vec<(String, String)>,
     pub history: bool,
 }
 
+impl Default for Rtd {
+    fn default() -> Self {
+        Self {
+            paths: Paths::default(),
+            conf: Arc::new(ArcSwap::from_pointee(Conf::default())),
+            overrides: Vec::new(),
+            history: false,
+        }
+    }
+}
+
+impl Clone for Rtd {
+    fn clone(&self) -> Self {
+        Self {
+            paths: self.paths.clone(),
+            conf: Arc::clone(&self.conf),
+            overrides: self.overrides.clone(),
+            history: self.history,
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct Paths {
     pub conf: PathBuf,
@@ -189,6 +697,13 @@ impl Rtd {
         self
     }
 
+    /// register dotted-path `section.field=value` overrides (e.g. from `--set`)
+    /// to be applied to the loaded configuration
+    pub fn set(&mut self, overrides: Vec<(String, String)>) -> &mut Self {
+        self.overrides = overrides;
+        self
+    }
+
     pub fn load(&mut self) -> Result<Self, Error> {
         ensure_parent_dir(&self.paths.conf)?;
 
@@ -201,29 +716,94 @@ impl Rtd {
             Conf::default().write(&self.paths.conf)?;
         }
 
-        // load config file
-        self.conf = Conf::load(&self.paths.conf)?;
+        let conf = self.resolve_conf()?;
 
-        self.paths.db = self.get_db_info().map(|p| expand_tilde(&p));
+        self.paths.db = self.get_db_info(&conf).map(|p| expand_tilde(&p));
 
         if let Some(dp) = &self.paths.db {
             ensure_parent_dir(dp)?;
         }
 
-        // set url-bot-rs version number in the irc client configuration
-        self.conf.client.version = Some(VERSION.to_string());
+        self.conf.store(Arc::new(conf));
 
         Ok(self.clone())
     }
 
-    fn get_db_info(&mut self) -> Option<PathBuf> {
-        if self.conf.features.history {
-            match self.conf.database.db_type {
+    /// read the configuration file and apply the full override stack on top:
+    /// `URLBOT_*` environment variables then command-line `--set` overrides
+    /// (precedence: file < environment < CLI), finally stamping the version
+    fn resolve_conf(&self) -> Result<Conf, Error> {
+        let mut conf = Conf::load(&self.paths.conf)?;
+
+        conf.apply_env(
+            std::env::vars().filter(|(k, _)| k.starts_with("URLBOT_"))
+        );
+        conf.apply_overrides(&self.overrides)?;
+
+        // set url-bot-rs version number in each network's irc configuration
+        for net in &mut conf.networks {
+            net.client.version = Some(VERSION.to_string());
+        }
+
+        Ok(conf)
+    }
+
+    /// the configuration currently in effect; cheap to call from the IRC loop
+    pub fn current_conf(&self) -> Arc<Conf> {
+        self.conf.load_full()
+    }
+
+    /// re-parse the configuration file and atomically swap it in, keeping the
+    /// previous good config if parsing fails
+    fn reload_conf(&self) -> Result<(), Error> {
+        let conf = self.resolve_conf()?;
+        self.conf.store(Arc::new(conf));
+        Ok(())
+    }
+
+    /// watch the configuration file and hot-reload it on change
+    ///
+    /// the returned watcher must be kept alive for the duration of the run;
+    /// dropping it stops the watch.
+    pub fn watch_conf(&self) -> Result<RecommendedWatcher, Error> {
+        let (tx, rx) = channel();
+        let mut watcher = notify::watcher(tx, Duration::from_secs(2))?;
+        watcher.watch(&self.paths.conf, RecursiveMode::NonRecursive)?;
+
+        let rtd = self.clone();
+        thread::spawn(move || {
+            for event in rx {
+                match event {
+                    DebouncedEvent::Write(_)
+                    | DebouncedEvent::Create(_)
+                    | DebouncedEvent::Rename(..) => {
+                        match rtd.reload_conf() {
+                            Ok(()) => info!(
+                                "reloaded configuration `{}`",
+                                rtd.paths.conf.display()
+                            ),
+                            Err(e) => error!(
+                                "failed to reload configuration, keeping \
+                                previous config: {}", e
+                            ),
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+
+    fn get_db_info(&self, conf: &Conf) -> Option<PathBuf> {
+        if conf.features.history {
+            match conf.database.db_type {
                 DbType::InMemory => { None },
                 DbType::SQLite => {
                     if let Some(p) = &self.paths.db {
                         Some(p.into())
-                    } else if let Some(p) = &self.conf.database.path {
+                    } else if let Some(p) = &conf.database.path {
                         Some(p.into())
                     } else {
                         None
@@ -248,6 +828,40 @@ macro_rules! impl_display {
 }
 impl_display!(Features, Parameters, Database);
 
+/// parse an environment override into `T`, warning (and leaving the field
+/// untouched) on failure
+fn parse_env<T>(key: &str, val: &str, set: impl FnOnce(T))
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match val.parse::<T>() {
+        Ok(v) => set(v),
+        Err(e) => warn!(
+            "ignoring `{}`: cannot parse `{}`: {}", key, val, e
+        ),
+    }
+}
+
+/// parse a dotted-path override value into `T`, with a descriptive error
+fn parse_override<T>(path: &str, val: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    val.parse::<T>().map_err(|e| {
+        format_err!("invalid value `{}` for `{}`: {}", val, path, e)
+    })
+}
+
+/// split a comma-separated environment value into a trimmed, non-empty list
+fn split_list(val: &str) -> Vec<String> {
+    val.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
 fn ensure_parent_dir(file: &Path) -> Result<bool, Error> {
     let without_path = file.components().count() == 1;
 
@@ -369,30 +983,185 @@ mod tests {
 
     #[test]
     fn conf_add_remove_channel() {
-        let mut rtd = Rtd::default();
+        let rtd = Rtd::default();
         check_channels(&rtd, "#url-bot-rs", 1);
 
-        rtd.conf.add_channel("#cheese".to_string());
+        mutate_conf(&rtd, |c| c.add_channel("#cheese".to_string()));
         check_channels(&rtd, "#cheese", 2);
 
-        rtd.conf.add_channel("#cheese-2".to_string());
+        mutate_conf(&rtd, |c| c.add_channel("#cheese-2".to_string()));
         check_channels(&rtd, "#cheese-2", 3);
 
-        rtd.conf.remove_channel(&"#cheese-2".to_string());
-        let c = rtd.conf.client.channels.clone().unwrap();
+        mutate_conf(&rtd, |c| c.remove_channel("#cheese-2"));
+        let c = rtd.current_conf().networks[0].client.channels.clone().unwrap();
 
         assert!(!c.contains(&"#cheese-2".to_string()));
         assert_eq!(2, c.len());
     }
 
+    /// load the current config, apply `f`, and atomically swap it back in
+    fn mutate_conf(rtd: &Rtd, f: impl FnOnce(&mut Conf)) {
+        let mut conf = (*rtd.current_conf()).clone();
+        f(&mut conf);
+        rtd.conf.store(Arc::new(conf));
+    }
+
     fn check_channels(rtd: &Rtd, contains: &str, len: usize) {
-        let c = rtd.conf.client.channels.clone().unwrap();
+        let c = rtd.current_conf().networks[0].client.channels.clone().unwrap();
         println!("{:?}", c);
 
         assert!(c.contains(&contains.to_string()));
         assert_eq!(len, c.len());
     }
 
+    #[test]
+    fn apply_env_overrides() {
+        let mut conf = Conf::default();
+        let vars = vec![
+            ("URLBOT_CONNECTION_SERVER".to_string(), "irc.libera.chat".to_string()),
+            ("URLBOT_CONNECTION_PORT".to_string(), "6697".to_string()),
+            ("URLBOT_PARAMETERS_URL_LIMIT".to_string(), "5".to_string()),
+            ("URLBOT_PARAMETERS_STATUS_CHANNELS".to_string(), "#a, #b".to_string()),
+            ("URLBOT_FEATURES_REPORT_METADATA".to_string(), "true".to_string()),
+            // unrecognized keys and bad values are ignored, not fatal
+            ("URLBOT_NONSENSE".to_string(), "x".to_string()),
+            ("URLBOT_CONNECTION_PORT".to_string(), "notanumber".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ];
+        conf.apply_env(vars.into_iter());
+
+        assert_eq!(conf.networks[0].client.server,
+            Some("irc.libera.chat".to_string()));
+        assert_eq!(conf.params.url_limit, 5);
+        assert_eq!(conf.params.status_channels,
+            vec!["#a".to_string(), "#b".to_string()]);
+        assert!(conf.features.report_metadata);
+        // the last (invalid) port value leaves the earlier good one in place
+        assert_eq!(conf.networks[0].client.port, Some(6697));
+    }
+
+    #[test]
+    /// a legacy single-`connection` config parses as a one-element network list
+    fn legacy_single_connection_parses() {
+        let toml = "\
+            [network]\n\
+            name = \"libera\"\n\
+            [connection]\n\
+            server = \"irc.libera.chat\"\n\
+        ";
+        let conf: Conf = toml::de::from_str(toml).unwrap();
+        assert_eq!(conf.networks.len(), 1);
+        assert_eq!(conf.networks[0].name, "libera");
+        assert_eq!(conf.networks[0].client.server,
+            Some("irc.libera.chat".to_string()));
+    }
+
+    #[test]
+    /// a modern `[[network]]` array parses into multiple networks, with
+    /// per-network overrides resolved against the global defaults
+    fn multi_network_parses_and_resolves() {
+        let toml = "\
+            [features]\n\
+            report_metadata = true\n\
+            [[network]]\n\
+            name = \"libera\"\n\
+            [network.connection]\n\
+            server = \"irc.libera.chat\"\n\
+            [[network]]\n\
+            name = \"oftc\"\n\
+            [network.connection]\n\
+            server = \"irc.oftc.net\"\n\
+            [network.features]\n\
+            report_metadata = false\n\
+        ";
+        let conf: Conf = toml::de::from_str(toml).unwrap();
+        assert_eq!(conf.networks.len(), 2);
+
+        let ctx = conf.contexts();
+        // first network inherits the global feature default
+        assert!(ctx[0].features.report_metadata);
+        // second network overrides it
+        assert!(!ctx[1].features.report_metadata);
+        assert_eq!(ctx[1].client.server, Some("irc.oftc.net".to_string()));
+    }
+
+    #[test]
+    /// the default config round-trips through each supported file format
+    fn load_write_roundtrip_formats() {
+        let tmp_dir = tempdir().unwrap();
+
+        for ext in &["toml", "json", "yaml", "yml"] {
+            let path = tmp_dir.path().join(format!("config.{}", ext));
+            let original = Conf::default();
+            original.write(&path).unwrap();
+
+            let loaded = Conf::load(&path).unwrap();
+            assert_eq!(loaded.networks[0].client.server,
+                original.networks[0].client.server);
+            assert_eq!(loaded.params.url_limit, original.params.url_limit);
+            assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        }
+    }
+
+    #[test]
+    /// an unversioned (version 0) document is migrated up to the current schema
+    fn migrate_unversioned_document() {
+        let doc = "[connection]\nserver = \"irc.libera.chat\"\n";
+        assert_eq!(Format::Toml.document_version(doc).unwrap(), 0);
+
+        let conf = Format::Toml.parse(doc).unwrap();
+        assert_eq!(conf.schema_version, SCHEMA_VERSION);
+        assert_eq!(conf.networks[0].client.server,
+            Some("irc.libera.chat".to_string()));
+    }
+
+    #[test]
+    /// a document from a future schema version is refused
+    fn migrate_future_version_refused() {
+        let doc = format!("schema_version = {}\n", SCHEMA_VERSION + 1);
+        assert!(Format::Toml.parse(&doc).is_err());
+    }
+
+    #[test]
+    fn apply_overrides_and_errors() {
+        let mut conf = Conf::default();
+        conf.apply_overrides(&[
+            ("connection.server".to_string(), "irc.libera.chat".to_string()),
+            ("parameters.url_limit".to_string(), "3".to_string()),
+            ("features.history".to_string(), "true".to_string()),
+        ]).unwrap();
+
+        assert_eq!(conf.networks[0].client.server,
+            Some("irc.libera.chat".to_string()));
+        assert_eq!(conf.params.url_limit, 3);
+        assert!(conf.features.history);
+
+        // unknown paths and type mismatches are reported as errors
+        assert!(conf.apply_overrides(
+            &[("features.nonsense".to_string(), "true".to_string())]).is_err());
+        assert!(conf.apply_overrides(
+            &[("parameters.url_limit".to_string(), "big".to_string())]).is_err());
+    }
+
+    #[test]
+    fn conf_add_remove_channel_by_network() {
+        let mut conf = Conf::default();
+        conf.networks[0].name = "libera".into();
+
+        conf.add_channel_to("libera", "#cheese".to_string());
+        assert!(conf.networks[0].client.channels.clone().unwrap()
+            .contains(&"#cheese".to_string()));
+
+        // unknown network is a no-op
+        conf.add_channel_to("nope", "#void".to_string());
+        assert!(!conf.networks[0].client.channels.clone().unwrap()
+            .contains(&"#void".to_string()));
+
+        conf.remove_channel_from("libera", "#cheese");
+        assert!(!conf.networks[0].client.channels.clone().unwrap()
+            .contains(&"#cheese".to_string()));
+    }
+
     #[test]
     fn test_expand_tilde() {
         let homedir: PathBuf = BaseDirs::new()